@@ -0,0 +1,277 @@
+//! Allow/deny filtering so a consumer that can reach the server can only
+//! read the slice of the vault it's been scoped to, rather than every
+//! secret the `BWS_ACCESS_TOKEN` has access to.
+//!
+//! Rules are loaded at startup from an optional JSON config file plus
+//! `--allow`/`--block` flags, and can be reloaded with [`AccessControl::reload`].
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// A single allow/deny rule. `organization_id` and `project_id` match
+/// exactly when present; `key_pattern` is a glob (`*` matches any run of
+/// characters). Any field left unset matches everything for that field.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct SecretRule {
+    pub organization_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub key_pattern: Option<String>,
+}
+
+impl SecretRule {
+    fn matches(&self, organization_id: Uuid, project_id: Option<Uuid>, key: &str) -> bool {
+        if let Some(expected) = self.organization_id {
+            if expected != organization_id {
+                return false;
+            }
+        }
+        if let Some(expected) = self.project_id {
+            if project_id != Some(expected) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.key_pattern {
+            if !glob_match(pattern, key) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Parses the `--allow`/`--block` flag shorthand: `<org_id|*>:<key_pattern>`.
+    pub fn parse_flag(flag: &str) -> Result<Self, String> {
+        let (org_part, key_pattern) = flag
+            .split_once(':')
+            .ok_or_else(|| format!("expected `<org_id|*>:<key_pattern>`, got '{flag}'"))?;
+
+        let organization_id = if org_part == "*" {
+            None
+        } else {
+            Some(
+                Uuid::parse_str(org_part)
+                    .map_err(|e| format!("invalid organization id '{org_part}': {e}"))?,
+            )
+        };
+
+        Ok(Self {
+            organization_id,
+            project_id: None,
+            key_pattern: Some(key_pattern.to_string()),
+        })
+    }
+}
+
+/// Minimal `*`-wildcard glob matcher, good enough for key patterns like
+/// `prod-*` without pulling in a dedicated glob crate.
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+
+    let mut rest = value;
+    let last = parts.len() - 1;
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(stripped) => rest = stripped,
+                None => return false,
+            }
+        } else if i == last {
+            if !rest.ends_with(part) {
+                return false;
+            }
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+#[derive(Deserialize, Default)]
+struct RuleFile {
+    #[serde(default)]
+    allow: Vec<SecretRule>,
+    #[serde(default)]
+    deny: Vec<SecretRule>,
+}
+
+struct Rules {
+    allow: Vec<SecretRule>,
+    deny: Vec<SecretRule>,
+}
+
+/// Config-driven filter consulted by `get_secret_handler` after a secret is
+/// resolved but before its value is returned.
+pub struct AccessControl {
+    config_path: Option<String>,
+    cli_allow: Vec<SecretRule>,
+    cli_deny: Vec<SecretRule>,
+    rules: RwLock<Rules>,
+}
+
+impl AccessControl {
+    /// Loads rules from `config_path` (if given) merged with the rules
+    /// already parsed from `--allow`/`--block` flags.
+    pub fn load(
+        config_path: Option<String>,
+        cli_allow: Vec<SecretRule>,
+        cli_deny: Vec<SecretRule>,
+    ) -> Result<Self, String> {
+        let rules = Self::read_rules(config_path.as_deref(), &cli_allow, &cli_deny)?;
+        Ok(Self {
+            config_path,
+            cli_allow,
+            cli_deny,
+            rules: RwLock::new(rules),
+        })
+    }
+
+    fn read_rules(
+        config_path: Option<&str>,
+        cli_allow: &[SecretRule],
+        cli_deny: &[SecretRule],
+    ) -> Result<Rules, String> {
+        let mut file_rules = RuleFile::default();
+        if let Some(path) = config_path {
+            if Path::new(path).exists() {
+                let contents = fs::read_to_string(path)
+                    .map_err(|e| format!("failed to read {path}: {e}"))?;
+                file_rules = serde_json::from_str(&contents)
+                    .map_err(|e| format!("failed to parse {path}: {e}"))?;
+            }
+        }
+
+        let mut allow = file_rules.allow;
+        allow.extend(cli_allow.iter().cloned());
+        let mut deny = file_rules.deny;
+        deny.extend(cli_deny.iter().cloned());
+
+        Ok(Rules { allow, deny })
+    }
+
+    /// Re-reads the config file from disk, keeping CLI-provided rules.
+    pub async fn reload(&self) -> Result<(), String> {
+        let rules = Self::read_rules(self.config_path.as_deref(), &self.cli_allow, &self.cli_deny)?;
+        *self.rules.write().await = rules;
+        Ok(())
+    }
+
+    /// Returns `true` if `(organization_id, project_id, key)` is allowed
+    /// through. A denylist match always wins; otherwise the secret must
+    /// match the allowlist, or the allowlist must be empty (meaning "allow
+    /// all").
+    pub async fn is_allowed(&self, organization_id: Uuid, project_id: Option<Uuid>, key: &str) -> bool {
+        let rules = self.rules.read().await;
+        if rules.deny.iter().any(|r| r.matches(organization_id, project_id, key)) {
+            return false;
+        }
+        rules.allow.is_empty()
+            || rules.allow.iter().any(|r| r.matches(organization_id, project_id, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_plain_equality_without_wildcard() {
+        assert!(glob_match("prod-db", "prod-db"));
+        assert!(!glob_match("prod-db", "prod-db-2"));
+    }
+
+    #[test]
+    fn glob_match_trailing_wildcard() {
+        assert!(glob_match("prod-*", "prod-db"));
+        assert!(glob_match("prod-*", "prod-"));
+        assert!(!glob_match("prod-*", "staging-db"));
+    }
+
+    #[test]
+    fn glob_match_leading_and_infix_wildcard() {
+        assert!(glob_match("*-db", "prod-db"));
+        assert!(glob_match("prod-*-key", "prod-east-key"));
+        assert!(!glob_match("prod-*-key", "staging-east-key"));
+    }
+
+    #[test]
+    fn parse_flag_rejects_missing_separator() {
+        assert!(SecretRule::parse_flag("not-a-valid-flag").is_err());
+    }
+
+    #[test]
+    fn parse_flag_accepts_wildcard_org() {
+        let rule = SecretRule::parse_flag("*:prod-*").unwrap();
+        assert!(rule.organization_id.is_none());
+        assert_eq!(rule.key_pattern.as_deref(), Some("prod-*"));
+    }
+
+    #[test]
+    fn parse_flag_rejects_invalid_org_id() {
+        assert!(SecretRule::parse_flag("not-a-uuid:prod-*").is_err());
+    }
+
+    fn org(n: u8) -> Uuid {
+        Uuid::from_bytes([n; 16])
+    }
+
+    #[tokio::test]
+    async fn empty_allowlist_allows_everything_not_denied() {
+        let acl = AccessControl::load(None, vec![], vec![]).unwrap();
+        assert!(acl.is_allowed(org(1), None, "anything").await);
+    }
+
+    #[tokio::test]
+    async fn deny_rule_always_wins_over_allow() {
+        let allow = vec![SecretRule {
+            organization_id: Some(org(1)),
+            project_id: None,
+            key_pattern: None,
+        }];
+        let deny = vec![SecretRule {
+            organization_id: Some(org(1)),
+            project_id: None,
+            key_pattern: Some("prod-*".to_string()),
+        }];
+        let acl = AccessControl::load(None, allow, deny).unwrap();
+        assert!(!acl.is_allowed(org(1), None, "prod-db").await);
+        assert!(acl.is_allowed(org(1), None, "staging-db").await);
+    }
+
+    #[tokio::test]
+    async fn non_empty_allowlist_blocks_unlisted_secrets() {
+        let allow = vec![SecretRule {
+            organization_id: Some(org(1)),
+            project_id: None,
+            key_pattern: Some("prod-*".to_string()),
+        }];
+        let acl = AccessControl::load(None, allow, vec![]).unwrap();
+        assert!(acl.is_allowed(org(1), None, "prod-db").await);
+        assert!(!acl.is_allowed(org(1), None, "staging-db").await);
+        assert!(!acl.is_allowed(org(2), None, "prod-db").await);
+    }
+
+    #[tokio::test]
+    async fn project_scoped_rule_does_not_match_other_projects() {
+        let allow = vec![SecretRule {
+            organization_id: None,
+            project_id: Some(org(9)),
+            key_pattern: None,
+        }];
+        let acl = AccessControl::load(None, allow, vec![]).unwrap();
+        assert!(acl.is_allowed(org(1), Some(org(9)), "anything").await);
+        assert!(!acl.is_allowed(org(1), Some(org(8)), "anything").await);
+        assert!(!acl.is_allowed(org(1), None, "anything").await);
+    }
+}
@@ -0,0 +1,327 @@
+//! Native client for the Bitwarden Secrets Manager API.
+//!
+//! Replaces shelling out to the `bws` CLI: we authenticate the machine
+//! account access token against the identity service ourselves (mirroring
+//! rbw's `api::Client`) and talk to the secrets REST API directly over
+//! `reqwest`.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+const DEFAULT_API_URL: &str = "https://api.bitwarden.com";
+const DEFAULT_IDENTITY_URL: &str = "https://identity.bitwarden.com";
+
+/// How long a bearer token is trusted before we re-authenticate.
+/// Bitwarden issues these with a one hour lifetime; we refresh early.
+const BEARER_TOKEN_LIFETIME: Duration = Duration::from_secs(55 * 60);
+
+#[derive(Debug)]
+pub enum BwsError {
+    InvalidAccessToken,
+    Auth(String),
+    Request(reqwest::Error),
+    Api { status: reqwest::StatusCode, body: String },
+}
+
+impl std::fmt::Display for BwsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BwsError::InvalidAccessToken => write!(f, "malformed BWS_ACCESS_TOKEN"),
+            BwsError::Auth(msg) => write!(f, "authentication with identity service failed: {msg}"),
+            BwsError::Request(e) => write!(f, "request to Bitwarden API failed: {e}"),
+            BwsError::Api { status, body } => {
+                write!(f, "Bitwarden API returned {status}: {body}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BwsError {}
+
+impl From<reqwest::Error> for BwsError {
+    fn from(e: reqwest::Error) -> Self {
+        BwsError::Request(e)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SecretResponse {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BwsSecretResponse {
+    pub id: String,
+    #[serde(rename = "organizationId")]
+    pub organization_id: String,
+    #[serde(rename = "projectId")]
+    pub project_id: Option<String>,
+    pub key: String,
+    pub value: String,
+    pub note: String,
+    #[serde(rename = "creationDate")]
+    pub creation_date: String,
+    #[serde(rename = "revisionDate")]
+    pub revision_date: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BwsSecretIdentifier {
+    pub id: String,
+    #[serde(rename = "organizationId")]
+    pub organization_id: String,
+    #[serde(rename = "projectId")]
+    pub project_id: Option<String>,
+    pub key: String,
+    #[serde(rename = "creationDate")]
+    pub creation_date: String,
+    #[serde(rename = "revisionDate")]
+    pub revision_date: String,
+}
+
+#[derive(Deserialize)]
+struct SecretIdentifiersResponse {
+    secrets: Vec<BwsSecretIdentifier>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSecretRequest {
+    pub organization_id: String,
+    pub project_id: String,
+    pub key: String,
+    pub value: String,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSecretRequest {
+    pub project_id: String,
+    pub key: String,
+    pub value: String,
+    pub note: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// The three parts encoded in a machine account access token:
+/// `0.<client_id>.<client_secret>:<encryption_key>`.
+struct ParsedAccessToken {
+    client_id: String,
+    client_secret: String,
+}
+
+impl ParsedAccessToken {
+    fn parse(raw: &str) -> Result<Self, BwsError> {
+        let (body, _encryption_key) = raw.split_once(':').ok_or(BwsError::InvalidAccessToken)?;
+        let mut parts = body.splitn(3, '.');
+        let _version = parts.next().ok_or(BwsError::InvalidAccessToken)?;
+        let client_id = parts.next().ok_or(BwsError::InvalidAccessToken)?.to_string();
+        let client_secret = parts.next().ok_or(BwsError::InvalidAccessToken)?.to_string();
+        Ok(Self { client_id, client_secret })
+    }
+}
+
+struct CachedBearer {
+    token: String,
+    fetched_at: Instant,
+}
+
+/// In-process client for the Secrets Manager API, authenticated with a
+/// `BWS_ACCESS_TOKEN` machine account token.
+pub struct Client {
+    http: reqwest::Client,
+    api_url: String,
+    identity_url: String,
+    access_token: ParsedAccessToken,
+    bearer: RwLock<Option<CachedBearer>>,
+}
+
+impl Client {
+    pub fn new(api_url: impl Into<String>, identity_url: impl Into<String>, raw_access_token: &str) -> Result<Self, BwsError> {
+        Ok(Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.into(),
+            identity_url: identity_url.into(),
+            access_token: ParsedAccessToken::parse(raw_access_token)?,
+            bearer: RwLock::new(None),
+        })
+    }
+
+    /// Builds a client from `BWS_ACCESS_TOKEN`, talking to the production
+    /// Bitwarden API and identity service.
+    pub fn from_env() -> Result<Self, BwsError> {
+        let raw_access_token =
+            std::env::var("BWS_ACCESS_TOKEN").map_err(|_| BwsError::InvalidAccessToken)?;
+        Self::new(DEFAULT_API_URL, DEFAULT_IDENTITY_URL, &raw_access_token)
+    }
+
+    /// Exchanges the machine account's client credentials for a bearer
+    /// token, caching it until it's close to expiry.
+    async fn bearer_token(&self) -> Result<String, BwsError> {
+        if let Some(cached) = self.bearer.read().await.as_ref() {
+            if cached.fetched_at.elapsed() < BEARER_TOKEN_LIFETIME {
+                return Ok(cached.token.clone());
+            }
+        }
+
+        let resp = self
+            .http
+            .post(format!("{}/connect/token", self.identity_url))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", self.access_token.client_id.as_str()),
+                ("client_secret", self.access_token.client_secret.as_str()),
+                ("scope", "api.secrets"),
+                ("device_type", "21"),
+                ("device_identifier", "mcp-bitwarden"),
+                ("device_name", "mcp-bitwarden"),
+            ])
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(BwsError::Auth(format!("{status}: {body}")));
+        }
+
+        let token: TokenResponse = resp.json().await?;
+        *self.bearer.write().await = Some(CachedBearer {
+            token: token.access_token.clone(),
+            fetched_at: Instant::now(),
+        });
+        Ok(token.access_token)
+    }
+
+    async fn parse_response<T: for<'de> Deserialize<'de>>(
+        resp: reqwest::Response,
+    ) -> Result<T, BwsError> {
+        let status = resp.status();
+        let body = resp.text().await?;
+        if !status.is_success() {
+            return Err(BwsError::Api { status, body });
+        }
+        serde_json::from_str(&body).map_err(|e| BwsError::Api {
+            status,
+            body: format!("failed to parse response: {e}"),
+        })
+    }
+
+    pub async fn list_secrets(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<BwsSecretIdentifier>, BwsError> {
+        let bearer = self.bearer_token().await?;
+        let resp = self
+            .http
+            .get(format!(
+                "{}/organizations/{}/secrets",
+                self.api_url, organization_id
+            ))
+            .bearer_auth(bearer)
+            .send()
+            .await?;
+        let wrapper: SecretIdentifiersResponse = Self::parse_response(resp).await?;
+        Ok(wrapper.secrets)
+    }
+
+    pub async fn get_secret(&self, secret_id: &str) -> Result<BwsSecretResponse, BwsError> {
+        let bearer = self.bearer_token().await?;
+        let resp = self
+            .http
+            .get(format!("{}/secrets/{}", self.api_url, secret_id))
+            .bearer_auth(bearer)
+            .send()
+            .await?;
+        Self::parse_response(resp).await
+    }
+
+    pub async fn create_secret(
+        &self,
+        req: &CreateSecretRequest,
+    ) -> Result<BwsSecretResponse, BwsError> {
+        let bearer = self.bearer_token().await?;
+        let resp = self
+            .http
+            .post(format!("{}/secrets", self.api_url))
+            .bearer_auth(bearer)
+            .json(req)
+            .send()
+            .await?;
+        Self::parse_response(resp).await
+    }
+
+    pub async fn update_secret(
+        &self,
+        secret_id: &str,
+        req: &UpdateSecretRequest,
+    ) -> Result<BwsSecretResponse, BwsError> {
+        let bearer = self.bearer_token().await?;
+        let resp = self
+            .http
+            .put(format!("{}/secrets/{}", self.api_url, secret_id))
+            .bearer_auth(bearer)
+            .json(req)
+            .send()
+            .await?;
+        Self::parse_response(resp).await
+    }
+
+    pub async fn delete_secret(&self, secret_id: &str) -> Result<(), BwsError> {
+        let bearer = self.bearer_token().await?;
+        let resp = self
+            .http
+            .delete(format!("{}/secrets", self.api_url))
+            .bearer_auth(bearer)
+            .json(&serde_json::json!({ "ids": [secret_id] }))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(BwsError::Api { status, body });
+        }
+        Ok(())
+    }
+
+    /// Verifies the machine account can still authenticate, for use by the
+    /// health check instead of shelling out to `bws --version`.
+    pub async fn check_connectivity(&self) -> bool {
+        // Drop any cached bearer so this always performs a real token exchange.
+        *self.bearer.write().await = None;
+        self.bearer_token().await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_client_id_and_secret_out_of_a_well_formed_token() {
+        let parsed = ParsedAccessToken::parse("0.client-id.client-secret:encryption-key").unwrap();
+        assert_eq!(parsed.client_id, "client-id");
+        assert_eq!(parsed.client_secret, "client-secret");
+    }
+
+    #[test]
+    fn rejects_token_missing_the_encryption_key_separator() {
+        let err = ParsedAccessToken::parse("0.client-id.client-secret").unwrap_err();
+        assert!(matches!(err, BwsError::InvalidAccessToken));
+    }
+
+    #[test]
+    fn rejects_token_missing_the_client_secret_segment() {
+        let err = ParsedAccessToken::parse("0.client-id:encryption-key").unwrap_err();
+        assert!(matches!(err, BwsError::InvalidAccessToken));
+    }
+}
@@ -0,0 +1,198 @@
+//! In-memory secret cache with revision-based invalidation.
+//!
+//! Listing and fetching every secret from Bitwarden on each request is slow
+//! enough to be noticeable, so we keep decrypted secrets hot in memory and
+//! only re-fetch the ones whose `revisionDate` actually changed.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::bws_client::{BwsError, Client};
+use crate::metrics::Metrics;
+
+#[derive(Clone)]
+pub struct CachedSecret {
+    pub id: String,
+    pub value: String,
+    pub revision_date: String,
+    pub project_id: Option<Uuid>,
+}
+
+/// Holds decrypted secrets keyed by `(organization_id, key)`, refreshed
+/// against Bitwarden on a TTL and invalidated per request via
+/// `POST /cache/refresh`.
+pub struct SecretCache {
+    client: Arc<Client>,
+    metrics: Arc<Metrics>,
+    ttl: Duration,
+    entries: RwLock<HashMap<(Uuid, String), CachedSecret>>,
+    known_orgs: RwLock<HashSet<Uuid>>,
+    last_refresh: RwLock<Option<Instant>>,
+}
+
+impl SecretCache {
+    pub fn new(client: Arc<Client>, metrics: Arc<Metrics>, ttl: Duration) -> Self {
+        Self {
+            client,
+            metrics,
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            known_orgs: RwLock::new(HashSet::new()),
+            last_refresh: RwLock::new(None),
+        }
+    }
+
+    /// Looks up a cached secret, refreshing first if the TTL has elapsed.
+    /// An organization seen for the first time always gets an immediate
+    /// refresh, rather than waiting for the next TTL tick, since it can't
+    /// have anything in the cache yet.
+    ///
+    /// Records `cache_hits_total` when the answer was served straight from
+    /// memory, and `cache_misses_total` when answering it required a round
+    /// trip to Bitwarden first -- this is about cache effectiveness, not
+    /// whether `key` actually resolved to a secret.
+    pub async fn get(&self, organization_id: Uuid, key: &str) -> Option<CachedSecret> {
+        let is_new_org = self.known_orgs.write().await.insert(organization_id);
+        let refreshed = if is_new_org {
+            if let Err(e) = self.refresh().await {
+                eprintln!("secret cache refresh failed: {}", e);
+            }
+            true
+        } else {
+            self.ensure_fresh().await
+        };
+
+        if refreshed {
+            self.metrics.cache_misses_total.inc();
+        } else {
+            self.metrics.cache_hits_total.inc();
+        }
+
+        self.entries
+            .read()
+            .await
+            .get(&(organization_id, key.to_string()))
+            .cloned()
+    }
+
+    /// Refreshes from upstream if the TTL has elapsed, returning whether it did.
+    async fn ensure_fresh(&self) -> bool {
+        let stale = match *self.last_refresh.read().await {
+            None => true,
+            Some(fetched_at) => fetched_at.elapsed() >= self.ttl,
+        };
+        if stale {
+            if let Err(e) = self.refresh().await {
+                eprintln!("secret cache refresh failed: {}", e);
+            }
+        }
+        stale
+    }
+
+    /// Re-lists every known organization and re-fetches only the secrets
+    /// whose `revisionDate` changed since last time, evicting ids that
+    /// disappeared.
+    pub async fn refresh(&self) -> Result<(), BwsError> {
+        let orgs: Vec<Uuid> = self.known_orgs.read().await.iter().copied().collect();
+        let mut still_present: HashSet<String> = HashSet::new();
+
+        for organization_id in orgs {
+            let identifiers = match self.client.list_secrets(organization_id).await {
+                Ok(identifiers) => identifiers,
+                Err(e) => {
+                    self.metrics.upstream_errors_total.inc();
+                    return Err(e);
+                }
+            };
+
+            let known_revisions: HashMap<String, String> = {
+                let entries = self.entries.read().await;
+                entries
+                    .values()
+                    .map(|c| (c.id.clone(), c.revision_date.clone()))
+                    .collect()
+            };
+
+            for identifier in &identifiers {
+                still_present.insert(identifier.id.clone());
+
+                if known_revisions.get(&identifier.id) == Some(&identifier.revision_date) {
+                    continue;
+                }
+
+                match self.client.get_secret(&identifier.id).await {
+                    Ok(secret) => {
+                        let project_id = secret
+                            .project_id
+                            .as_deref()
+                            .and_then(|id| Uuid::parse_str(id).ok());
+                        let mut entries = self.entries.write().await;
+                        // The secret may have been renamed upstream since we
+                        // last cached it; its id survives, but the old
+                        // (org, old_key) entry would otherwise never be
+                        // evicted since the id is still "present".
+                        let stale_key = entries
+                            .iter()
+                            .find(|((org, key), cached)| {
+                                *org == organization_id && cached.id == secret.id && *key != secret.key
+                            })
+                            .map(|(k, _)| k.clone());
+                        if let Some(stale_key) = stale_key {
+                            entries.remove(&stale_key);
+                        }
+                        entries.insert(
+                            (organization_id, secret.key.clone()),
+                            CachedSecret {
+                                id: secret.id,
+                                value: secret.value,
+                                revision_date: secret.revision_date,
+                                project_id,
+                            },
+                        );
+                    }
+                    Err(e) => {
+                        self.metrics.upstream_errors_total.inc();
+                        eprintln!(
+                            "failed to refresh secret '{}' ({}): {}",
+                            identifier.key, identifier.id, e
+                        );
+                    }
+                }
+            }
+        }
+
+        self.entries
+            .write()
+            .await
+            .retain(|_, cached| still_present.contains(&cached.id));
+        *self.last_refresh.write().await = Some(Instant::now());
+        Ok(())
+    }
+
+    pub async fn len(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    pub async fn last_refresh(&self) -> Option<Instant> {
+        *self.last_refresh.read().await
+    }
+
+    /// Spawns a background task that re-runs `refresh` on the configured
+    /// TTL for as long as the server is up.
+    pub fn spawn_background_refresh(self: &Arc<Self>) {
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(cache.ttl);
+            loop {
+                interval.tick().await;
+                if let Err(e) = cache.refresh().await {
+                    eprintln!("background secret cache refresh failed: {}", e);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,220 @@
+//! CLI argument parsing plus a small figment-style config layer: a TOML
+//! config file overlaid with environment variables, overlaid with
+//! command-line flags (CLI wins, then env, then file, then defaults).
+
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use clap::Parser;
+use serde::Deserialize;
+
+const DEFAULT_HOST: [u8; 4] = [127, 0, 0, 1];
+const DEFAULT_PORT: u16 = 8080;
+const DEFAULT_CACHE_TTL_SECONDS: u64 = 300;
+
+#[derive(Parser, Debug)]
+#[command(name = "mcp-bitwarden", about = "MCP server fronting Bitwarden Secrets Manager")]
+pub struct Cli {
+    /// Path to a TOML config file.
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Address to bind the server to.
+    #[arg(long)]
+    pub host: Option<IpAddr>,
+
+    /// Port to bind the server to.
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// TLS certificate (PEM). Requires --tls-key; serves HTTPS instead of plaintext HTTP.
+    #[arg(long, value_name = "FILE")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// TLS private key (PEM), paired with --tls-cert.
+    #[arg(long, value_name = "FILE")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Allow a secret matching `<org_id|*>:<key_pattern>`. Repeatable.
+    #[arg(long = "allow", value_name = "RULE")]
+    pub allow: Vec<String>,
+
+    /// Block a secret matching `<org_id|*>:<key_pattern>`. Repeatable.
+    #[arg(long = "block", value_name = "RULE")]
+    pub block: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct FileConfig {
+    host: Option<IpAddr>,
+    port: Option<u16>,
+    tls_cert: Option<PathBuf>,
+    tls_key: Option<PathBuf>,
+    access_control_config: Option<PathBuf>,
+    cache_ttl_seconds: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct ServerConfig {
+    pub host: IpAddr,
+    pub port: u16,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    pub access_control_config: Option<PathBuf>,
+    pub cache_ttl_seconds: u64,
+}
+
+impl ServerConfig {
+    /// Merges the config file (if `--config` points at one), environment
+    /// overrides, and CLI flags, with CLI flags taking precedence.
+    pub fn load(cli: &Cli) -> Result<Self, String> {
+        let file_config = match &cli.config {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|e| format!("failed to read config file {}: {e}", path.display()))?;
+                toml::from_str(&contents)
+                    .map_err(|e| format!("failed to parse config file {}: {e}", path.display()))?
+            }
+            None => FileConfig::default(),
+        };
+
+        let host = cli
+            .host
+            .or_else(|| env_parsed("MCP_HOST"))
+            .or(file_config.host)
+            .unwrap_or_else(|| IpAddr::from(DEFAULT_HOST));
+
+        let port = cli
+            .port
+            .or_else(|| env_parsed("MCP_PORT"))
+            .or(file_config.port)
+            .unwrap_or(DEFAULT_PORT);
+
+        let tls_cert = cli.tls_cert.clone().or(file_config.tls_cert);
+        let tls_key = cli.tls_key.clone().or(file_config.tls_key);
+
+        let access_control_config = std::env::var("ACCESS_CONTROL_CONFIG")
+            .ok()
+            .map(PathBuf::from)
+            .or(file_config.access_control_config);
+
+        let cache_ttl_seconds = env_parsed("CACHE_TTL_SECONDS")
+            .or(file_config.cache_ttl_seconds)
+            .unwrap_or(DEFAULT_CACHE_TTL_SECONDS);
+
+        if tls_cert.is_some() != tls_key.is_some() {
+            return Err("--tls-cert and --tls-key must both be set to enable TLS".to_string());
+        }
+
+        Ok(Self {
+            host,
+            port,
+            tls_cert,
+            tls_key,
+            access_control_config,
+            cache_ttl_seconds,
+        })
+    }
+}
+
+fn env_parsed<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    // `ServerConfig::load` reads process-global env vars, so any test that
+    // sets one (directly or via `EnvVarGuard`) needs to hold this lock for
+    // as long as the var is set, or a test running on another thread could
+    // observe it.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock_env() -> std::sync::MutexGuard<'static, ()> {
+        ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sets an env var for the lifetime of the guard, holding `ENV_LOCK` the
+    /// whole time, and removes the var on drop (including on panic/unwind)
+    /// so a failed assertion can't leak it into later tests.
+    struct EnvVarGuard<'a> {
+        name: &'static str,
+        _lock: std::sync::MutexGuard<'a, ()>,
+    }
+
+    impl<'a> EnvVarGuard<'a> {
+        fn set(lock: std::sync::MutexGuard<'a, ()>, name: &'static str, value: &str) -> Self {
+            unsafe {
+                std::env::set_var(name, value);
+            }
+            Self { name, _lock: lock }
+        }
+    }
+
+    impl Drop for EnvVarGuard<'_> {
+        fn drop(&mut self) {
+            unsafe {
+                std::env::remove_var(self.name);
+            }
+        }
+    }
+
+    fn empty_cli() -> Cli {
+        Cli {
+            config: None,
+            host: None,
+            port: None,
+            tls_cert: None,
+            tls_key: None,
+            allow: vec![],
+            block: vec![],
+        }
+    }
+
+    /// Guards against the precedence regression where the file config was
+    /// checked before the environment, contradicting the module's documented
+    /// "CLI wins, then env, then file, then defaults" order.
+    #[test]
+    fn env_overrides_file_but_cli_overrides_env() {
+        let _env = EnvVarGuard::set(lock_env(), "MCP_PORT", "9100");
+
+        let path = std::env::temp_dir().join(format!("mcp-bitwarden-test-{}.toml", std::process::id()));
+        std::fs::write(&path, "port = 9000\n").unwrap();
+
+        let mut cli = empty_cli();
+        cli.config = Some(path.clone());
+        let config = ServerConfig::load(&cli).unwrap();
+        assert_eq!(config.port, 9100, "env should win over the file");
+
+        cli.port = Some(9200);
+        let config = ServerConfig::load(&cli).unwrap();
+        assert_eq!(config.port, 9200, "CLI should win over env");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn falls_back_to_file_then_default_when_cli_and_env_unset() {
+        // MCP_PORT must be unset for this test's assertions to hold, so it
+        // has to exclude `env_overrides_file_but_cli_overrides_env` too.
+        let _lock = lock_env();
+
+        let path = std::env::temp_dir().join(format!("mcp-bitwarden-test-{}-fallback.toml", std::process::id()));
+        std::fs::write(&path, "port = 9000\n").unwrap();
+
+        let mut cli = empty_cli();
+        cli.config = Some(path.clone());
+        let config = ServerConfig::load(&cli).unwrap();
+        assert_eq!(config.port, 9000);
+
+        cli.config = None;
+        let config = ServerConfig::load(&cli).unwrap();
+        assert_eq!(config.port, DEFAULT_PORT);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,254 +1,397 @@
+mod access_control;
+mod bws_client;
+mod cache;
+mod config;
+mod mcp;
+mod metrics;
+
 use std::convert::Infallible;
-use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
 use warp::{Filter, Reply, http::StatusCode};
-use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Serialize)]
-struct SecretResponse {
-    key: String,
-    value: String,
-}
-
-#[derive(Deserialize)]
-struct BwsSecretResponse {
-    id: String,
-    #[serde(rename = "organizationId")]
-    organization_id: String,
-    #[serde(rename = "projectId")]
-    project_id: String,
-    key: String,
-    value: String,
-    note: String,
-    #[serde(rename = "creationDate")]
-    creation_date: String,
-    #[serde(rename = "revisionDate")]
-    revision_date: String,
-}
-
-#[derive(Deserialize)]
-struct BwsSecretIdentifier {
-    id: String,
-    #[serde(rename = "organizationId")]
-    organization_id: String,
-    #[serde(rename = "projectId")]
-    project_id: String,
-    key: String,
-    #[serde(rename = "creationDate")]
-    creation_date: String,
-    #[serde(rename = "revisionDate")]
-    revision_date: String,
-}
+use access_control::{AccessControl, SecretRule};
+use bws_client::{Client, CreateSecretRequest, SecretResponse, UpdateSecretRequest};
+use cache::SecretCache;
+use config::{Cli, ServerConfig};
+use mcp::JsonRpcRequest;
+use metrics::Metrics;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Verify BWS_ACCESS_TOKEN is set
-    std::env::var("BWS_ACCESS_TOKEN")
-        .expect("BWS_ACCESS_TOKEN must be set in environment");
+    let cli = Cli::parse();
+    let server_config = ServerConfig::load(&cli)?;
+
+    let client = Arc::new(Client::from_env()?);
+    let metrics = Arc::new(Metrics::new());
 
-    println!("Using bws CLI wrapper approach");
+    let cache = Arc::new(SecretCache::new(
+        client.clone(),
+        metrics.clone(),
+        Duration::from_secs(server_config.cache_ttl_seconds),
+    ));
+    cache.spawn_background_refresh();
+
+    let cli_allow = cli
+        .allow
+        .iter()
+        .map(|flag| SecretRule::parse_flag(flag))
+        .collect::<Result<Vec<_>, _>>()?;
+    let cli_deny = cli
+        .block
+        .iter()
+        .map(|flag| SecretRule::parse_flag(flag))
+        .collect::<Result<Vec<_>, _>>()?;
+    let access_control_config = server_config
+        .access_control_config
+        .as_ref()
+        .map(|p| p.to_string_lossy().into_owned());
+    let access_control = Arc::new(
+        AccessControl::load(access_control_config, cli_allow, cli_deny)
+            .map_err(|e| format!("access control: {e}"))?,
+    );
+
+    println!("Using native Bitwarden Secrets Manager API client");
+    println!("Secret cache TTL: {}s", server_config.cache_ttl_seconds);
+
+    let with_client = {
+        let client = client.clone();
+        warp::any().map(move || client.clone())
+    };
+    let with_cache = {
+        let cache = cache.clone();
+        warp::any().map(move || cache.clone())
+    };
+    let with_acl = {
+        let access_control = access_control.clone();
+        warp::any().map(move || access_control.clone())
+    };
+    let with_metrics = {
+        let metrics = metrics.clone();
+        warp::any().map(move || metrics.clone())
+    };
 
     let secret_route = warp::path!("secret" / String / String)
         .and(warp::get())
+        .and(with_cache.clone())
+        .and(with_acl.clone())
+        .and(with_metrics.clone())
         .and_then(get_secret_handler);
 
+    let create_secret_route = warp::path("secret")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_client.clone())
+        .and(with_cache.clone())
+        .and(with_metrics.clone())
+        .and_then(create_secret_handler);
+
+    let update_secret_route = warp::path!("secret" / String)
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(with_client.clone())
+        .and(with_cache.clone())
+        .and(with_metrics.clone())
+        .and_then(update_secret_handler);
+
+    let delete_secret_route = warp::path!("secret" / String)
+        .and(warp::delete())
+        .and(with_client.clone())
+        .and(with_cache.clone())
+        .and(with_metrics.clone())
+        .and_then(delete_secret_handler);
+
+    let cache_refresh_route = warp::path!("cache" / "refresh")
+        .and(warp::post())
+        .and(with_cache.clone())
+        .and_then(cache_refresh_handler);
+
+    let mcp_route = warp::path("mcp")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_cache.clone())
+        .and(with_acl.clone())
+        .and(with_metrics.clone())
+        .and_then(mcp_handler);
+
+    let metrics_route = warp::path("metrics")
+        .and(warp::get())
+        .and(with_metrics.clone())
+        .and_then(metrics_handler);
+
+    let status_route = warp::path("status")
+        .and(warp::get())
+        .and(with_cache.clone())
+        .and(with_client.clone())
+        .and_then(status_handler);
+
     // Add health check endpoint
     let health_route = warp::path("health")
         .and(warp::get())
+        .and(with_client.clone())
         .and_then(health_check);
 
-    let routes = secret_route.or(health_route);
+    let routes = secret_route
+        .or(create_secret_route)
+        .or(update_secret_route)
+        .or(delete_secret_route)
+        .or(cache_refresh_route)
+        .or(mcp_route)
+        .or(metrics_route)
+        .or(status_route)
+        .or(health_route);
 
-    println!("MCP server running at http://127.0.0.1:8080");
+    let scheme = if server_config.tls_cert.is_some() { "https" } else { "http" };
+    println!("MCP server running at {}://{}:{}", scheme, server_config.host, server_config.port);
     println!("Endpoints:");
     println!("  GET /secret/<org_id>/<secret_key>");
+    println!("  POST /secret");
+    println!("  PUT /secret/<id>");
+    println!("  DELETE /secret/<id>");
+    println!("  POST /cache/refresh");
+    println!("  POST /mcp (JSON-RPC 2.0)");
+    println!("  GET /metrics");
+    println!("  GET /status");
     println!("  GET /health");
 
-    warp::serve(routes).run(([127, 0, 0, 1], 8080)).await;
+    let addr = (server_config.host, server_config.port);
+    match (&server_config.tls_cert, &server_config.tls_key) {
+        (Some(cert), Some(key)) => {
+            warp::serve(routes)
+                .tls()
+                .cert_path(cert)
+                .key_path(key)
+                .run(addr)
+                .await;
+        }
+        _ => {
+            warp::serve(routes).run(addr).await;
+        }
+    }
 
     Ok(())
 }
 
-async fn health_check() -> Result<impl Reply, Infallible> {
-    // Test if bws command is available and working
-    match Command::new("bws").arg("--version").output() {
-        Ok(output) if output.status.success() => {
-            Ok(warp::reply::with_status("OK - bws CLI available", StatusCode::OK))
-        }
-        _ => {
-            Ok(warp::reply::with_status("ERROR - bws CLI not available", StatusCode::SERVICE_UNAVAILABLE))
+async fn health_check(client: Arc<Client>) -> Result<impl Reply, Infallible> {
+    if client.check_connectivity().await {
+        Ok(warp::reply::with_status("OK - Bitwarden API reachable", StatusCode::OK))
+    } else {
+        Ok(warp::reply::with_status(
+            "ERROR - could not authenticate with Bitwarden API",
+            StatusCode::SERVICE_UNAVAILABLE,
+        ))
+    }
+}
+
+async fn cache_refresh_handler(cache: Arc<SecretCache>) -> Result<impl Reply, Infallible> {
+    match cache.refresh().await {
+        Ok(()) => Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "status": "refreshed",
+                "cached_secrets": cache.len().await,
+            })),
+            StatusCode::OK,
+        )),
+        Err(e) => {
+            eprintln!("Forced cache refresh failed: {}", e);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": "Failed to refresh secret cache",
+                    "details": e.to_string(),
+                })),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
         }
     }
 }
 
-async fn get_secret_handler(
-    org_id_str: String,
-    secret_key: String,
+async fn metrics_handler(metrics: Arc<Metrics>) -> Result<impl Reply, Infallible> {
+    Ok(warp::reply::with_header(
+        metrics.render(),
+        "Content-Type",
+        "text/plain; version=0.0.4",
+    ))
+}
+
+async fn status_handler(
+    cache: Arc<SecretCache>,
+    client: Arc<Client>,
 ) -> Result<impl Reply, Infallible> {
-    let org_id = match Uuid::parse_str(&org_id_str) {
-        Ok(uuid) => uuid,
-        Err(_) => {
-            return Ok(
-                warp::reply::with_status(
-                    warp::reply::json(&serde_json::json!({
-                        "error": "Invalid organization ID format"
-                    })),
-                    StatusCode::BAD_REQUEST,
-                ).into_response()
-            );
-        }
-    };
+    let cached_secret_count = cache.len().await;
+    let last_refresh_seconds_ago = cache.last_refresh().await.map(|t| t.elapsed().as_secs_f64());
+    let upstream_reachable = client.check_connectivity().await;
 
-    println!("Attempting to find secret '{}' in org: {}", secret_key, org_id);
+    Ok(warp::reply::json(&serde_json::json!({
+        "cached_secret_count": cached_secret_count,
+        "last_refresh_seconds_ago": last_refresh_seconds_ago,
+        "upstream_reachable": upstream_reachable,
+    })))
+}
 
-    // Step 1: First, we need to list all secrets without specifying project
-    // The bws CLI works differently - let's try listing all secrets first
-    let list_output = Command::new("bws")
-        .args(&["secret", "list", "--output", "json"])
-        .output();
+async fn mcp_handler(
+    req: JsonRpcRequest,
+    cache: Arc<SecretCache>,
+    access_control: Arc<AccessControl>,
+    metrics: Arc<Metrics>,
+) -> Result<impl Reply, Infallible> {
+    let response = mcp::handle_request(cache, access_control, metrics, req).await;
+    Ok(warp::reply::json(&response))
+}
 
-    let list_result = match list_output {
-        Ok(output) if output.status.success() => {
-            String::from_utf8_lossy(&output.stdout).to_string()
-        }
-        Ok(output) => {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            eprintln!("bws list command failed: {}", error_msg);
-            return Ok(
-                warp::reply::with_status(
-                    warp::reply::json(&serde_json::json!({
-                        "error": "Failed to list secrets via bws CLI",
-                        "details": error_msg.to_string()
-                    })),
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                ).into_response()
-            );
+async fn create_secret_handler(
+    req: CreateSecretRequest,
+    client: Arc<Client>,
+    cache: Arc<SecretCache>,
+    metrics: Arc<Metrics>,
+) -> Result<impl Reply, Infallible> {
+    match client.create_secret(&req).await {
+        Ok(secret) => {
+            if let Err(e) = cache.refresh().await {
+                eprintln!("cache refresh after create failed: {}", e);
+            }
+            println!("Created secret '{}' ({})", secret.key, secret.id);
+            Ok(warp::reply::with_status(warp::reply::json(&secret), StatusCode::CREATED).into_response())
         }
         Err(e) => {
-            eprintln!("Failed to execute bws command: {}", e);
-            return Ok(
+            metrics.upstream_errors_total.inc();
+            eprintln!("Failed to create secret: {}", e);
+            Ok(
                 warp::reply::with_status(
                     warp::reply::json(&serde_json::json!({
-                        "error": "bws CLI not available",
+                        "error": "Failed to create secret via Bitwarden API",
                         "details": e.to_string()
                     })),
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    StatusCode::BAD_GATEWAY,
                 ).into_response()
-            );
+            )
         }
-    };
+    }
+}
 
-    // Parse the JSON response from bws list - it's a direct array, not wrapped in {data: [...]}
-    let secrets_list: Vec<BwsSecretIdentifier> = match serde_json::from_str(&list_result) {
-        Ok(list) => list,
+async fn update_secret_handler(
+    secret_id: String,
+    req: UpdateSecretRequest,
+    client: Arc<Client>,
+    cache: Arc<SecretCache>,
+    metrics: Arc<Metrics>,
+) -> Result<impl Reply, Infallible> {
+    match client.update_secret(&secret_id, &req).await {
+        Ok(secret) => {
+            if let Err(e) = cache.refresh().await {
+                eprintln!("cache refresh after update failed: {}", e);
+            }
+            println!("Updated secret '{}' ({})", secret.key, secret.id);
+            Ok(warp::reply::with_status(warp::reply::json(&secret), StatusCode::OK).into_response())
+        }
         Err(e) => {
-            eprintln!("Failed to parse bws list output: {}", e);
-            eprintln!("Raw output: {}", list_result);
-            return Ok(
+            metrics.upstream_errors_total.inc();
+            eprintln!("Failed to update secret {}: {}", secret_id, e);
+            Ok(
                 warp::reply::with_status(
                     warp::reply::json(&serde_json::json!({
-                        "error": "Failed to parse bws output",
+                        "error": "Failed to update secret via Bitwarden API",
                         "details": e.to_string()
                     })),
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    StatusCode::BAD_GATEWAY,
                 ).into_response()
-            );
+            )
         }
-    };
-
-    // Find the secret with matching key and organization
-    let secret_identifier = secrets_list.iter().find(|s| 
-        s.key == secret_key && s.organization_id == org_id.to_string()
-    );
+    }
+}
 
-    let secret_id = match secret_identifier {
-        Some(identifier) => {
-            println!("Found secret '{}' with ID: {}", secret_key, identifier.id);
-            &identifier.id
+async fn delete_secret_handler(
+    secret_id: String,
+    client: Arc<Client>,
+    cache: Arc<SecretCache>,
+    metrics: Arc<Metrics>,
+) -> Result<impl Reply, Infallible> {
+    match client.delete_secret(&secret_id).await {
+        Ok(()) => {
+            if let Err(e) = cache.refresh().await {
+                eprintln!("cache refresh after delete failed: {}", e);
+            }
+            println!("Deleted secret {}", secret_id);
+            Ok(warp::reply::with_status(warp::reply::json(&serde_json::json!({
+                "status": "deleted",
+                "id": secret_id,
+            })), StatusCode::OK).into_response())
         }
-        None => {
-            println!("Secret '{}' not found in org {}. Available secrets: {:?}", 
-                secret_key, 
-                org_id,
-                secrets_list.iter()
-                    .filter(|s| s.organization_id == org_id.to_string())
-                    .map(|s| &s.key)
-                    .collect::<Vec<_>>()
-            );
-            return Ok(
+        Err(e) => {
+            metrics.upstream_errors_total.inc();
+            eprintln!("Failed to delete secret {}: {}", secret_id, e);
+            Ok(
                 warp::reply::with_status(
                     warp::reply::json(&serde_json::json!({
-                        "error": format!("Secret '{}' not found in organization", secret_key),
-                        "available_secrets": secrets_list.iter()
-                            .filter(|s| s.organization_id == org_id.to_string())
-                            .map(|s| &s.key)
-                            .collect::<Vec<_>>()
+                        "error": "Failed to delete secret via Bitwarden API",
+                        "details": e.to_string()
                     })),
-                    StatusCode::NOT_FOUND,
+                    StatusCode::BAD_GATEWAY,
                 ).into_response()
-            );
+            )
         }
-    };
+    }
+}
 
-    // Step 2: Get the full secret details using the secret ID
-    let get_output = Command::new("bws")
-        .args(&["secret", "get", secret_id, "--output", "json"])
-        .output();
+async fn get_secret_handler(
+    org_id_str: String,
+    secret_key: String,
+    cache: Arc<SecretCache>,
+    access_control: Arc<AccessControl>,
+    metrics: Arc<Metrics>,
+) -> Result<impl Reply, Infallible> {
+    metrics.secret_lookups_total.inc();
+    let _timer = metrics.secret_lookup_duration_seconds.start_timer();
 
-    let secret_result = match get_output {
-        Ok(output) if output.status.success() => {
-            String::from_utf8_lossy(&output.stdout).to_string()
-        }
-        Ok(output) => {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            eprintln!("bws get command failed: {}", error_msg);
-            return Ok(
-                warp::reply::with_status(
-                    warp::reply::json(&serde_json::json!({
-                        "error": "Failed to get secret via bws CLI",
-                        "details": error_msg.to_string()
-                    })),
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                ).into_response()
-            );
-        }
-        Err(e) => {
-            eprintln!("Failed to execute bws get command: {}", e);
+    let org_id = match Uuid::parse_str(&org_id_str) {
+        Ok(uuid) => uuid,
+        Err(_) => {
             return Ok(
                 warp::reply::with_status(
                     warp::reply::json(&serde_json::json!({
-                        "error": "bws CLI execution failed",
-                        "details": e.to_string()
+                        "error": "Invalid organization ID format"
                     })),
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    StatusCode::BAD_REQUEST,
                 ).into_response()
             );
         }
     };
 
-    // Parse the secret details
-    let secret: BwsSecretResponse = match serde_json::from_str(&secret_result) {
-        Ok(secret) => secret,
-        Err(e) => {
-            eprintln!("Failed to parse bws get output: {}", e);
-            eprintln!("Raw output: {}", secret_result);
-            return Ok(
+    println!("Attempting to find secret '{}' in org: {}", secret_key, org_id);
+
+    match cache.get(org_id, &secret_key).await {
+        Some(cached) => {
+            if !access_control.is_allowed(org_id, cached.project_id, &secret_key).await {
+                println!("Secret '{}' in org {} blocked by access control", secret_key, org_id);
+                return Ok(
+                    warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({
+                            "error": format!("Secret '{}' is not accessible", secret_key)
+                        })),
+                        StatusCode::FORBIDDEN,
+                    ).into_response()
+                );
+            }
+            println!("Successfully returning secret for key: {}", secret_key);
+            let response = SecretResponse {
+                key: secret_key,
+                value: cached.value,
+            };
+            Ok(warp::reply::json(&response).into_response())
+        }
+        None => {
+            println!("Secret '{}' not found in org {}", secret_key, org_id);
+            Ok(
                 warp::reply::with_status(
                     warp::reply::json(&serde_json::json!({
-                        "error": "Failed to parse secret details",
-                        "details": e.to_string()
+                        "error": format!("Secret '{}' not found in organization", secret_key)
                     })),
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    StatusCode::NOT_FOUND,
                 ).into_response()
-            );
+            )
         }
-    };
-
-    let response = SecretResponse {
-        key: secret.key,
-        value: secret.value,
-    };
-
-    println!("Successfully returning secret for key: {}", secret_key);
-    Ok(warp::reply::json(&response).into_response())
-}
\ No newline at end of file
+    }
+}
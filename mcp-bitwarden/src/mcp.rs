@@ -0,0 +1,145 @@
+//! A genuine Model Context Protocol endpoint, speaking JSON-RPC 2.0 over
+//! HTTP. This is what lets an LLM agent discover the `get_secret` tool and
+//! call it instead of guessing at the `/secret/<org>/<key>` REST shape.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::access_control::AccessControl;
+use crate::cache::SecretCache;
+use crate::metrics::Metrics;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+pub struct JsonRpcRequest {
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Serialize)]
+pub struct JsonRpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<JsonRpcErrorBody>,
+}
+
+#[derive(Serialize)]
+struct JsonRpcErrorBody {
+    code: i64,
+    message: String,
+}
+
+impl JsonRpcResponse {
+    fn ok(id: Value, result: Value) -> Self {
+        Self { jsonrpc: "2.0", id, result: Some(result), error: None }
+    }
+
+    fn err(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(JsonRpcErrorBody { code, message: message.into() }),
+        }
+    }
+}
+
+/// Dispatches a single JSON-RPC call to its MCP method handler.
+pub async fn handle_request(
+    cache: Arc<SecretCache>,
+    access_control: Arc<AccessControl>,
+    metrics: Arc<Metrics>,
+    req: JsonRpcRequest,
+) -> JsonRpcResponse {
+    match req.method.as_str() {
+        "initialize" => JsonRpcResponse::ok(req.id, json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "mcp-bitwarden", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "tools/list" => JsonRpcResponse::ok(req.id, json!({ "tools": [get_secret_tool()] })),
+        "tools/call" => handle_tools_call(cache, access_control, metrics, req.id, req.params).await,
+        other => JsonRpcResponse::err(req.id, METHOD_NOT_FOUND, format!("Method not found: {other}")),
+    }
+}
+
+fn get_secret_tool() -> Value {
+    json!({
+        "name": "get_secret",
+        "description": "Fetch a Bitwarden Secrets Manager secret by organization and key.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "organization_id": {
+                    "type": "string",
+                    "description": "UUID of the Bitwarden organization the secret belongs to",
+                },
+                "secret_key": {
+                    "type": "string",
+                    "description": "The secret's key",
+                },
+            },
+            "required": ["organization_id", "secret_key"],
+        },
+    })
+}
+
+async fn handle_tools_call(
+    cache: Arc<SecretCache>,
+    access_control: Arc<AccessControl>,
+    metrics: Arc<Metrics>,
+    id: Value,
+    params: Value,
+) -> JsonRpcResponse {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+    if name != "get_secret" {
+        return JsonRpcResponse::err(id, INVALID_PARAMS, format!("Unknown tool: {name}"));
+    }
+
+    let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+    let org_id_str = arguments.get("organization_id").and_then(Value::as_str).unwrap_or_default();
+    let secret_key = arguments.get("secret_key").and_then(Value::as_str).unwrap_or_default();
+
+    let org_id = match Uuid::parse_str(org_id_str) {
+        Ok(uuid) => uuid,
+        Err(_) => return JsonRpcResponse::err(id, INVALID_PARAMS, "organization_id must be a UUID"),
+    };
+    if secret_key.is_empty() {
+        return JsonRpcResponse::err(id, INVALID_PARAMS, "secret_key is required");
+    }
+
+    metrics.secret_lookups_total.inc();
+    let _timer = metrics.secret_lookup_duration_seconds.start_timer();
+
+    match cache.get(org_id, secret_key).await {
+        Some(cached) => {
+            if !access_control.is_allowed(org_id, cached.project_id, secret_key).await {
+                return JsonRpcResponse::ok(id, json!({
+                    "content": [{ "type": "text", "text": format!("Secret '{secret_key}' is not accessible") }],
+                    "isError": true,
+                }));
+            }
+            JsonRpcResponse::ok(id, json!({
+                "content": [{ "type": "text", "text": cached.value }],
+                "isError": false,
+            }))
+        }
+        None => JsonRpcResponse::ok(id, json!({
+            "content": [{ "type": "text", "text": format!("Secret '{secret_key}' not found in organization") }],
+            "isError": true,
+        })),
+    }
+}
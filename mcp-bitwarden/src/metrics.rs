@@ -0,0 +1,86 @@
+//! Prometheus metrics, rendered for `GET /metrics`, so upstream outages
+//! or cache thrash show up somewhere other than stdout `eprintln!` logs.
+
+use prometheus::{Counter, Encoder, Histogram, HistogramOpts, Opts, Registry, TextEncoder};
+
+pub struct Metrics {
+    registry: Registry,
+    pub secret_lookups_total: Counter,
+    pub cache_hits_total: Counter,
+    pub cache_misses_total: Counter,
+    pub upstream_errors_total: Counter,
+    pub secret_lookup_duration_seconds: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let secret_lookups_total = Counter::with_opts(Opts::new(
+            "secret_lookups_total",
+            "Total number of secret lookups received via GET /secret or the MCP get_secret tool",
+        ))
+        .expect("valid counter opts");
+        let cache_hits_total = Counter::with_opts(Opts::new(
+            "cache_hits_total",
+            "Secret lookups served from the in-memory cache",
+        ))
+        .expect("valid counter opts");
+        let cache_misses_total = Counter::with_opts(Opts::new(
+            "cache_misses_total",
+            "Secret lookups that required a refresh from Bitwarden before answering",
+        ))
+        .expect("valid counter opts");
+        let upstream_errors_total = Counter::with_opts(Opts::new(
+            "upstream_errors_total",
+            "Errors returned by the Bitwarden Secrets Manager API",
+        ))
+        .expect("valid counter opts");
+        let secret_lookup_duration_seconds = Histogram::with_opts(HistogramOpts::new(
+            "secret_lookup_duration_seconds",
+            "Latency of secret lookups via GET /secret or the MCP get_secret tool",
+        ))
+        .expect("valid histogram opts");
+
+        registry
+            .register(Box::new(secret_lookups_total.clone()))
+            .expect("register secret_lookups_total");
+        registry
+            .register(Box::new(cache_hits_total.clone()))
+            .expect("register cache_hits_total");
+        registry
+            .register(Box::new(cache_misses_total.clone()))
+            .expect("register cache_misses_total");
+        registry
+            .register(Box::new(upstream_errors_total.clone()))
+            .expect("register upstream_errors_total");
+        registry
+            .register(Box::new(secret_lookup_duration_seconds.clone()))
+            .expect("register secret_lookup_duration_seconds");
+
+        Self {
+            registry,
+            secret_lookups_total,
+            cache_hits_total,
+            cache_misses_total,
+            upstream_errors_total,
+            secret_lookup_duration_seconds,
+        }
+    }
+
+    /// Renders all registered metrics in Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let metric_families = self.registry.gather();
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&metric_families, &mut buf)
+            .expect("encode metrics");
+        String::from_utf8(buf).unwrap_or_default()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}